@@ -2,7 +2,7 @@ use sqlx::Executor;
 use sqlx::Type;
 use sqlx::types::JsonValue;
 use async_trait::async_trait;
-use sqlx::{Database, FromRow, IntoArguments, Pool};
+use sqlx::{Database, FromRow, IntoArguments, MySql, Pool, Postgres, Sqlite};
 use std::marker::PhantomData;
 use serde::Serialize;
 use serde_json::Value;
@@ -34,6 +34,129 @@ struct Join {
     condition: String,
 }
 
+/// Describes the SQL rendering rules of a specific backend, so that
+/// `Query::to_sql` can produce dialect-correct output instead of assuming
+/// SQLite/MySQL-style `?` placeholders everywhere.
+pub trait Dialect {
+    /// Renders the placeholder for the `index`-th bound parameter (1-based).
+    fn placeholder(index: usize) -> String;
+
+    /// Quotes a table or column identifier for this backend.
+    fn quote_identifier(ident: &str) -> String;
+
+    /// Whether this backend supports a `RETURNING` clause.
+    fn supports_returning() -> bool;
+}
+
+impl Dialect for Sqlite {
+    fn placeholder(_index: usize) -> String {
+        "?".to_string()
+    }
+
+    fn quote_identifier(ident: &str) -> String {
+        format!("\"{}\"", ident)
+    }
+
+    fn supports_returning() -> bool {
+        true
+    }
+}
+
+impl Dialect for Postgres {
+    fn placeholder(index: usize) -> String {
+        format!("${}", index)
+    }
+
+    fn quote_identifier(ident: &str) -> String {
+        format!("\"{}\"", ident)
+    }
+
+    fn supports_returning() -> bool {
+        true
+    }
+}
+
+impl Dialect for MySql {
+    fn placeholder(_index: usize) -> String {
+        "?".to_string()
+    }
+
+    fn quote_identifier(ident: &str) -> String {
+        format!("`{}`", ident)
+    }
+
+    fn supports_returning() -> bool {
+        false
+    }
+}
+
+/// Rewrites literal `?` placeholders in `s` with `D`'s rendering, advancing
+/// `counter` for every replacement so WHERE, SET and VALUES placeholders
+/// share one monotonically increasing sequence. Returns the rewritten text
+/// and how many placeholders were rendered, so the caller can pull the
+/// matching number of bound parameters in order.
+///
+/// A `?` inside a single-quoted string literal (e.g. `'what?'`) is left
+/// alone rather than treated as a placeholder. Write `??` to emit a
+/// literal `?` outside of a string literal too — this matters on Postgres,
+/// where `?` is also the jsonb "key exists" operator (`data ? 'key'`).
+fn rewrite_placeholders<D: Dialect>(s: &str, counter: &mut usize) -> (String, usize) {
+    let mut out = String::with_capacity(s.len());
+    let mut in_quotes = false;
+    let mut replaced = 0;
+    let mut chars = s.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => {
+                // SQL escapes a quote inside a literal by doubling it ('').
+                if in_quotes && chars.peek() == Some(&'\'') {
+                    out.push(ch);
+                    out.push(chars.next().unwrap());
+                    continue;
+                }
+                in_quotes = !in_quotes;
+                out.push(ch);
+            }
+            '?' if !in_quotes => {
+                if chars.peek() == Some(&'?') {
+                    chars.next();
+                    out.push('?');
+                } else {
+                    out.push_str(&D::placeholder(*counter));
+                    *counter += 1;
+                    replaced += 1;
+                }
+            }
+            _ => out.push(ch),
+        }
+    }
+
+    (out, replaced)
+}
+
+/// A single WHERE condition: either a raw SQL fragment with literal `?`
+/// placeholders, or another `Query` embedded inline as a subquery.
+///
+/// The subquery variant exists because a raw `format!("... ({})", sub.build())`
+/// bakes the subquery's own placeholders into the fragment before the outer
+/// query ever sees it, so the outer counter can't tell they're already
+/// spoken for. Rendering the subquery lazily, from the outer counter's
+/// current position, keeps `$N` numbering (and bound parameter order)
+/// correct across the whole statement.
+#[derive(Debug, Clone)]
+enum Condition<DB>
+where
+    DB: Database,
+{
+    Raw(String),
+    Subquery {
+        prefix: String,
+        query: Box<Query<DB>>,
+        suffix: String,
+    },
+}
+
 /// Main structure for building SQL queries.
 #[derive(Debug, Clone)]
 pub struct Query<DB>
@@ -43,7 +166,7 @@ where
     query_type: QueryType,
     columns: Vec<String>,
     table: Option<String>,
-    conditions: Vec<String>,
+    conditions: Vec<Condition<DB>>,
     params: Vec<Json<Value>>, // Changed to sqlx::types::Json<Value>
     joins: Vec<Join>,
     values: Vec<Json<Value>>, // Changed to sqlx::types::Json<Value>
@@ -52,6 +175,7 @@ where
     order_by: Vec<String>,
     limit: Option<u64>,
     offset: Option<u64>,
+    returning: Vec<String>,
     _db_marker: PhantomData<DB>,
 }
 
@@ -80,6 +204,7 @@ where
             order_by: Vec::new(),
             limit: None,
             offset: None,
+            returning: Vec::new(),
             _db_marker: PhantomData,
         }
     }
@@ -108,6 +233,7 @@ where
             order_by: Vec::new(),
             limit: None,
             offset: None,
+            returning: Vec::new(),
             _db_marker: PhantomData,
         }
     }
@@ -136,6 +262,7 @@ where
             order_by: Vec::new(),
             limit: None,
             offset: None,
+            returning: Vec::new(),
             _db_marker: PhantomData,
         }
     }
@@ -164,6 +291,7 @@ where
             order_by: Vec::new(),
             limit: None,
             offset: None,
+            returning: Vec::new(),
             _db_marker: PhantomData,
         }
     }
@@ -209,7 +337,36 @@ where
     /// let query = Query::select().where_clause("age > 18");
     /// ```
     pub fn where_clause(mut self, condition: &str) -> Self {
-        self.conditions.push(condition.to_string());
+        self.conditions.push(Condition::Raw(condition.to_string()));
+        self
+    }
+
+    /// Adds a WHERE clause that embeds another `Query` as a subquery.
+    ///
+    /// Unlike splicing a pre-built `subquery.build()` string into
+    /// `where_clause` by hand, this renders the subquery lazily from the
+    /// outer query's current placeholder position, so `$N` numbering (and
+    /// bound parameter order) stays correct across both queries.
+    ///
+    /// # Arguments
+    /// * `prefix` - Text placed immediately before the rendered subquery, e.g. `"user_id IN ("`.
+    /// * `subquery` - The nested `Query` to render in place.
+    /// * `suffix` - Text placed immediately after the rendered subquery, e.g. `")"`.
+    ///
+    /// # Example
+    /// ```
+    /// use naturalquerylib::Query;
+    /// let subquery = Query::select().columns(&["id"]).from("users").where_clause("age > ?");
+    /// let query = Query::select()
+    ///     .from("employees")
+    ///     .where_subquery("user_id IN (", subquery, ")");
+    /// ```
+    pub fn where_subquery(mut self, prefix: &str, subquery: Query<DB>, suffix: &str) -> Self {
+        self.conditions.push(Condition::Subquery {
+            prefix: prefix.to_string(),
+            query: Box::new(subquery),
+            suffix: suffix.to_string(),
+        });
         self
     }
 
@@ -371,12 +528,66 @@ where
         self
     }
 
+    /// Adds a RETURNING clause to an INSERT/UPDATE/DELETE.
+    ///
+    /// Honoured only on backends where [`Dialect::supports_returning`]
+    /// returns `true`; on others it is silently dropped, since a backend
+    /// that doesn't support `RETURNING` has no equivalent to fall back to.
+    ///
+    /// # Arguments
+    /// * `cols` - A slice of column names to return.
+    ///
+    /// # Example
+    /// ```
+    /// use naturalquerylib::Query;
+    /// let query = Query::insert_into("users").returning(&["id"]);
+    /// ```
+    pub fn returning(mut self, cols: &[&str]) -> Self {
+        self.returning = cols.iter().map(|&s| s.to_string()).collect();
+        self
+    }
+}
+
+impl<DB> Query<DB>
+where
+    DB: Database + Dialect,
+{
     /// Builds the SQL query string.
     ///
+    /// Discards the bound parameters produced by [`Query::to_sql`]; use that
+    /// method directly if the caller needs to bind them.
+    ///
     /// # Returns
     /// The constructed SQL query as a `String`.
     pub fn build(&self) -> String {
+        self.to_sql().0
+    }
+
+    /// Builds the SQL query and the parameters bound to its placeholders,
+    /// rendered for the backend `DB` (`?` for SQLite/MySQL, `$1`, `$2`, …
+    /// for Postgres).
+    ///
+    /// WHERE, SET and VALUES placeholders share one monotonically
+    /// increasing counter, so a single query that mixes, say, UPDATE's SET
+    /// values with a WHERE clause still gets consistent numbering. A
+    /// subquery added via [`Query::where_subquery`] is rendered from that
+    /// same counter, so nesting never collides placeholder numbers.
+    ///
+    /// # Returns
+    /// A tuple of the SQL text and the parameters in placeholder order.
+    pub fn to_sql(&self) -> (String, Vec<Json<Value>>) {
+        let (sql, params, _next) = self.render(1);
+        (sql, params)
+    }
+
+    /// Does the actual rendering, starting the placeholder counter at
+    /// `start` and returning the counter's next free value alongside the
+    /// SQL and params, so a parent query can pick up numbering where a
+    /// nested [`Condition::Subquery`] left off.
+    fn render(&self, start: usize) -> (String, Vec<Json<Value>>, usize) {
         let mut query = String::new();
+        let mut params = Vec::new();
+        let mut counter = start;
 
         match self.query_type {
             QueryType::Select => {
@@ -388,39 +599,60 @@ where
                 query.push_str(&format!("SELECT {} ", cols));
             }
             QueryType::Insert => {
-                let cols = self.columns.join(", ");
-                let placeholders: Vec<String> = (0..self.values.len())
-                    .map(|_| "?".to_string())
+                let cols: Vec<String> = self
+                    .columns
+                    .iter()
+                    .map(|col| DB::quote_identifier(col))
+                    .collect();
+                let placeholders: Vec<String> = self
+                    .values
+                    .iter()
+                    .map(|_| {
+                        let p = DB::placeholder(counter);
+                        counter += 1;
+                        p
+                    })
                     .collect();
-                let placeholders_str = placeholders.join(", ");
+                params.extend(self.values.iter().cloned());
 
                 query.push_str(&format!(
                     "INSERT INTO {} ({}) VALUES ({}) ",
-                    self.table.as_ref().unwrap(),
-                    cols,
-                    placeholders_str
+                    DB::quote_identifier(self.table.as_ref().unwrap()),
+                    cols.join(", "),
+                    placeholders.join(", ")
                 ));
             }
             QueryType::Update => {
                 let set_clauses: Vec<String> = self
                     .columns
                     .iter()
-                    .map(|col| format!("{} = ?", col))
+                    .map(|col| {
+                        let p = DB::placeholder(counter);
+                        counter += 1;
+                        format!("{} = {}", DB::quote_identifier(col), p)
+                    })
                     .collect();
+                params.extend(self.values.iter().cloned());
 
                 query.push_str(&format!(
                     "UPDATE {} SET {} ",
-                    self.table.as_ref().unwrap(),
+                    DB::quote_identifier(self.table.as_ref().unwrap()),
                     set_clauses.join(", ")
                 ));
             }
             QueryType::Delete => {
-                query.push_str(&format!("DELETE FROM {} ", self.table.as_ref().unwrap()));
+                query.push_str(&format!(
+                    "DELETE FROM {} ",
+                    DB::quote_identifier(self.table.as_ref().unwrap())
+                ));
             }
         }
 
         if let Some(table) = &self.table {
             if matches!(self.query_type, QueryType::Select) {
+                // Unlike INSERT/UPDATE/DELETE's table, a SELECT's `table`
+                // may carry a join alias (e.g. "users u"), so it's treated
+                // as a raw fragment rather than a single quotable identifier.
                 query.push_str(&format!("FROM {} ", table));
             }
         }
@@ -442,7 +674,31 @@ where
 
         if !self.conditions.is_empty() {
             query.push_str("WHERE ");
-            query.push_str(&self.conditions.join(" AND "));
+            let mut raw_params = self.params.iter().cloned();
+            let mut rendered = Vec::with_capacity(self.conditions.len());
+
+            for condition in &self.conditions {
+                match condition {
+                    Condition::Raw(raw) => {
+                        let (text, placeholder_count) =
+                            rewrite_placeholders::<DB>(raw, &mut counter);
+                        params.extend((&mut raw_params).take(placeholder_count));
+                        rendered.push(text);
+                    }
+                    Condition::Subquery {
+                        prefix,
+                        query: subquery,
+                        suffix,
+                    } => {
+                        let (sub_sql, sub_params, next_counter) = subquery.render(counter);
+                        counter = next_counter;
+                        params.extend(sub_params);
+                        rendered.push(format!("{}{}{}", prefix, sub_sql, suffix));
+                    }
+                }
+            }
+
+            query.push_str(&rendered.join(" AND "));
             query.push(' ');
         }
 
@@ -466,7 +722,16 @@ where
             query.push_str(&format!("OFFSET {} ", offset));
         }
 
-        query.trim_end().to_string()
+        if !self.returning.is_empty() && DB::supports_returning() {
+            let cols: Vec<String> = self
+                .returning
+                .iter()
+                .map(|col| DB::quote_identifier(col))
+                .collect();
+            query.push_str(&format!("RETURNING {} ", cols.join(", ")));
+        }
+
+        (query.trim_end().to_string(), params, counter)
     }
 }
 
@@ -550,6 +815,113 @@ mod tests {
             "SELECT name FROM employees WHERE user_id IN (SELECT id FROM users WHERE age > ?)"
         );
     }
+
+    /// Test that Postgres renders `$1`, `$2`, ... instead of `?`.
+    #[tokio::test]
+    async fn test_postgres_select_placeholders() {
+        let query = Query::<Postgres>::select()
+            .columns(&["id", "name"])
+            .from("users")
+            .where_clause("age > ? AND name = ?");
+
+        let sql = query.build();
+
+        assert_eq!(
+            sql,
+            "SELECT id, name FROM users WHERE age > $1 AND name = $2"
+        );
+    }
+
+    /// Test that an UPDATE's SET placeholders and its WHERE placeholders
+    /// share one monotonically increasing counter, and that the table and
+    /// column names are quoted for the target dialect.
+    #[tokio::test]
+    async fn test_postgres_update_placeholders_are_monotonic() {
+        let (sql, params) = Query::<Postgres>::update("users")
+            .set(&[("name", "Jane Doe")])
+            .where_clause("id = ?")
+            .to_sql();
+
+        assert_eq!(sql, "UPDATE \"users\" SET \"name\" = $1 WHERE id = $2");
+        assert_eq!(params.len(), 1);
+    }
+
+    /// Test that a `?` inside a quoted string literal is left alone rather
+    /// than consumed as a placeholder.
+    #[tokio::test]
+    async fn test_question_mark_in_string_literal_is_not_a_placeholder() {
+        let query = Query::<Postgres>::select()
+            .from("users")
+            .where_clause("comment LIKE '%?%' AND id = ?");
+
+        let sql = query.build();
+
+        assert_eq!(sql, "SELECT * FROM users WHERE comment LIKE '%?%' AND id = $1");
+    }
+
+    /// Test that `??` escapes to a literal `?`, e.g. Postgres's jsonb
+    /// "key exists" operator, without consuming a placeholder slot.
+    #[tokio::test]
+    async fn test_double_question_mark_escapes_to_literal() {
+        let query = Query::<Postgres>::select()
+            .from("docs")
+            .where_clause("data ?? 'key' AND id = ?");
+
+        let sql = query.build();
+
+        assert_eq!(sql, "SELECT * FROM docs WHERE data ? 'key' AND id = $1");
+    }
+
+    /// Test that a subquery embedded via `where_subquery` is renumbered
+    /// from the outer query's counter instead of colliding with it.
+    #[tokio::test]
+    async fn test_postgres_subquery_placeholders_do_not_collide() {
+        let subquery = Query::<Postgres>::select()
+            .columns(&["id"])
+            .from("users")
+            .where_clause("age > ?")
+            .add_param(18);
+
+        let main_query = Query::<Postgres>::select()
+            .columns(&["name"])
+            .from("employees")
+            .where_subquery("user_id IN (", subquery, ")")
+            .where_clause("status = ?")
+            .add_param("active");
+
+        let (sql, params) = main_query.to_sql();
+
+        assert_eq!(
+            sql,
+            "SELECT name FROM employees WHERE user_id IN (SELECT id FROM users WHERE age > $1) AND status = $2"
+        );
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].0, serde_json::json!(18));
+        assert_eq!(params[1].0, serde_json::json!("active"));
+    }
+
+    /// Test that RETURNING is only emitted for dialects that support it.
+    #[tokio::test]
+    async fn test_returning_is_gated_by_dialect_support() {
+        let postgres_sql = Query::<Postgres>::insert_into("users")
+            .columns(&["name"])
+            .values(&["Jane Doe"])
+            .returning(&["id"])
+            .build();
+
+        assert_eq!(
+            postgres_sql,
+            "INSERT INTO \"users\" (\"name\") VALUES ($1) RETURNING \"id\""
+        );
+
+        let mysql_sql = Query::<MySql>::insert_into("users")
+            .columns(&["name"])
+            .values(&["Jane Doe"])
+            .returning(&["id"])
+            .build();
+
+        assert_eq!(mysql_sql, "INSERT INTO `users` (`name`) VALUES (?)");
+    }
 }
 
 